@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Shape {
     Pawn,
@@ -27,17 +29,47 @@ impl Color {
 
 pub use Color::*;
 
+/// The rank pawns of `color` start on, derived from the same setup rows
+/// `Board::new` places them on so the two can never drift apart.
+fn pawn_start_y(color: Color) -> Coordinate {
+    match color {
+        White => -3,
+        Black => 2,
+    }
+}
+
+/// The direction, in `y`, that a pawn of `color` moves and captures.
+fn pawn_forward(color: Color) -> Coordinate {
+    match color {
+        White => 1,
+        Black => -1,
+    }
+}
+
+/// The rank `color`'s king and rooks start on, derived from the same setup
+/// rows `Board::new` places them on.
+fn back_rank_y(color: Color) -> Coordinate {
+    match color {
+        White => -4,
+        Black => 3,
+    }
+}
+
 pub type Coordinate = i128;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Location(Coordinate, Coordinate);
 
 impl Location {
-    fn x(self) -> Coordinate {
+    pub fn new(x: Coordinate, y: Coordinate) -> Location {
+        Location(x, y)
+    }
+
+    pub fn x(self) -> Coordinate {
         self.0
     }
 
-    fn y(self) -> Coordinate {
+    pub fn y(self) -> Coordinate {
         self.1
     }
 }
@@ -64,6 +96,17 @@ impl CastleDataEntry {
             queenside_rook_moved: false,
         }
     }
+
+    /// Builds an entry from which sides castling is still available on,
+    /// since that's all a `KQkq`-style flag or a [`BoardBuilder`] caller can
+    /// say, not specifically whether the king or that rook moved.
+    fn from_rights(kingside: bool, queenside: bool) -> CastleDataEntry {
+        CastleDataEntry {
+            king_moved: !kingside && !queenside,
+            kingdside_rook_moved: !kingside,
+            queenside_rook_moved: !queenside,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -80,17 +123,120 @@ impl CastleData {
         }
     }
 
+    /// No castling rights for either color, the right starting point for a
+    /// board assembled piece by piece rather than from the standard setup.
+    fn none() -> CastleData {
+        CastleData {
+            white: CastleDataEntry::from_rights(false, false),
+            black: CastleDataEntry::from_rights(false, false),
+        }
+    }
+
     fn for_color(self, color: Color) -> CastleDataEntry {
         match color {
             White => self.white,
             Black => self.black,
         }
     }
+
+    fn for_color_mut(&mut self, color: Color) -> &mut CastleDataEntry {
+        match color {
+            White => &mut self.white,
+            Black => &mut self.black,
+        }
+    }
+}
+
+/// A spatial index over a board's pieces, keyed every way a ray can run on
+/// an infinite board: by file, by rank, by diagonal (`x - y` constant), and
+/// by anti-diagonal (`x + y` constant). Each of those is a `BTreeMap` so the
+/// nearest occupant in either direction along a ray is an O(log n) lookup
+/// instead of a scan over every piece. `by_location` gives O(1) `piece_at`.
+///
+/// The index stores indices into `Board::pieces`, so it is only valid until
+/// the next time that vector is mutated; `Board::reindex` rebuilds it.
+#[derive(Clone)]
+struct BoardIndex {
+    by_location: HashMap<Location, usize>,
+    by_file: HashMap<Coordinate, BTreeMap<Coordinate, usize>>,
+    by_rank: HashMap<Coordinate, BTreeMap<Coordinate, usize>>,
+    by_diagonal: HashMap<Coordinate, BTreeMap<Coordinate, usize>>,
+    by_antidiagonal: HashMap<Coordinate, BTreeMap<Coordinate, usize>>,
+}
+
+impl BoardIndex {
+    fn empty() -> BoardIndex {
+        BoardIndex {
+            by_location: HashMap::new(),
+            by_file: HashMap::new(),
+            by_rank: HashMap::new(),
+            by_diagonal: HashMap::new(),
+            by_antidiagonal: HashMap::new(),
+        }
+    }
+
+    fn build(pieces: &[Piece]) -> BoardIndex {
+        let mut index = BoardIndex::empty();
+        for (idx, piece) in pieces.iter().enumerate() {
+            index.insert(idx, piece.location);
+        }
+        index
+    }
+
+    fn insert(&mut self, idx: usize, location: Location) {
+        self.by_location.insert(location, idx);
+        self.by_file.entry(location.x()).or_default().insert(location.y(), idx);
+        self.by_rank.entry(location.y()).or_default().insert(location.x(), idx);
+        self.by_diagonal.entry(location.x() - location.y()).or_default().insert(location.x(), idx);
+        self.by_antidiagonal.entry(location.x() + location.y()).or_default().insert(location.x(), idx);
+    }
+
+    /// Removes whatever is indexed at `location`, returning its former
+    /// `pieces` index.
+    fn remove(&mut self, location: Location) -> Option<usize> {
+        let idx = self.by_location.remove(&location)?;
+        if let Some(column) = self.by_file.get_mut(&location.x()) {
+            column.remove(&location.y());
+            if column.is_empty() {
+                self.by_file.remove(&location.x());
+            }
+        }
+        if let Some(row) = self.by_rank.get_mut(&location.y()) {
+            row.remove(&location.x());
+            if row.is_empty() {
+                self.by_rank.remove(&location.y());
+            }
+        }
+        if let Some(diagonal) = self.by_diagonal.get_mut(&(location.x() - location.y())) {
+            diagonal.remove(&location.x());
+            if diagonal.is_empty() {
+                self.by_diagonal.remove(&(location.x() - location.y()));
+            }
+        }
+        if let Some(diagonal) = self.by_antidiagonal.get_mut(&(location.x() + location.y())) {
+            diagonal.remove(&location.x());
+            if diagonal.is_empty() {
+                self.by_antidiagonal.remove(&(location.x() + location.y()));
+            }
+        }
+        Some(idx)
+    }
 }
 
+const KNIGHT_OFFSETS: [(Coordinate, Coordinate); 8] = [
+    (1, 2), (1, -2), (-1, 2), (-1, -2),
+    (2, 1), (2, -1), (-2, 1), (-2, -1),
+];
+
 pub struct Board {
     pieces: Vec<Piece>,
     castle_data: CastleData,
+    index: BoardIndex,
+    /// The square a pawn can capture onto en passant, i.e. the square
+    /// directly behind the pawn that just advanced two ranks. Cleared after
+    /// every move that isn't that double push.
+    en_passant: Option<Location>,
+    to_move: Color,
 }
 
 impl Board {
@@ -98,14 +244,13 @@ impl Board {
         let mut pieces = Vec::with_capacity(32);
 
         // Set up the pieces for each color.
-        //     base_y = Rank where the pieces go.
-        //     pawn_y = Rank where the pawns go.
-        for (color, base_y, pawn_y) in [(White, -4, -3), (Black, 3, 2)] {
+        for color in [White, Black] {
+            let base_y = back_rank_y(color);
             for x in -4..3 {
                 pieces.push(Piece {
                     color,
                     shape: Pawn,
-                    location: Location(x, pawn_y),
+                    location: Location(x, pawn_start_y(color)),
                 });
             }
             for (shape, files) in [(Rook, [-4, 3]), (Knight, [-3, 2]), (Bishop, [-2, 1])] {
@@ -128,20 +273,28 @@ impl Board {
                 location: Location(0, base_y),
             });
         }
-        Board { pieces, castle_data: CastleData::new() }
+        let index = BoardIndex::build(&pieces);
+        Board { pieces, castle_data: CastleData::new(), index, en_passant: None, to_move: White }
     }
 
     pub fn new_blank() -> Board {
-        Board { pieces: Vec::new(), castle_data: CastleData::new() }
+        Board {
+            pieces: Vec::new(),
+            castle_data: CastleData::new(),
+            index: BoardIndex::empty(),
+            en_passant: None,
+            to_move: White,
+        }
+    }
+
+    /// The color whose turn it is to move.
+    pub fn to_move(&self) -> Color {
+        self.to_move
     }
 
     pub fn piece_at(&self, location: Location) -> Option<BoardPiece> {
-        for &piece in &self.pieces {
-            if piece.location == location {
-                return Some(self.board_piece(piece));
-            }
-        }
-        None
+        let &idx = self.index.by_location.get(&location)?;
+        Some(self.board_piece(self.pieces[idx]))
     }
 
     pub fn pieces(&self) -> impl Iterator<Item = BoardPiece> {
@@ -162,22 +315,754 @@ impl Board {
         &mut self.pieces
     }
 
+    /// Rebuilds the spatial index from the current piece list. Call this
+    /// after mutating the board directly through [`Board::raw_board`].
+    pub fn reindex(&mut self) {
+        self.index = BoardIndex::build(&self.pieces);
+    }
+
+    /// Adds a piece, keeping the spatial index in sync.
+    fn push_piece(&mut self, piece: Piece) {
+        let idx = self.pieces.len();
+        self.pieces.push(piece);
+        self.index.insert(idx, piece.location);
+    }
+
+    /// Removes whatever piece sits at `location`, keeping the spatial index
+    /// in sync, including re-pointing the index entry for the piece that
+    /// `Vec::swap_remove` moves into the vacated slot.
+    fn remove_piece_at(&mut self, location: Location) -> Option<Piece> {
+        let idx = self.index.remove(location)?;
+        let piece = self.pieces.swap_remove(idx);
+        if let Some(&moved) = self.pieces.get(idx) {
+            self.index.insert(idx, moved.location);
+        }
+        Some(piece)
+    }
+
+    /// Moves the piece at `from` to `to`, keeping the spatial index in sync.
+    fn relocate_piece(&mut self, from: Location, to: Location) {
+        let Some(idx) = self.index.remove(from) else {
+            return;
+        };
+        self.pieces[idx].location = to;
+        self.index.insert(idx, to);
+    }
+
+    /// Changes the shape of the piece at `location` in place, e.g. for pawn
+    /// promotion. Does not affect the spatial index, since location is
+    /// unchanged.
+    fn set_shape_at(&mut self, location: Location, shape: Shape) {
+        if let Some(&idx) = self.index.by_location.get(&location) {
+            self.pieces[idx].shape = shape;
+        }
+    }
+
     pub fn find_attackers_of(&self, location: Location, check_legal: bool, color: Option<Color>) -> Box<dyn Iterator<Item = BoardPiece> + '_> {
-        match color {
-            Some(color) => Box::new(self.pieces().filter(move |&piece| color == piece.color() && piece.attack_sight(location, check_legal).is_legal())),
-            None => Box::new(self.pieces().filter(move |&piece| piece.attack_sight(location, check_legal).is_legal())),
+        let mut candidates = self.attacker_candidates(location);
+        if let Some(color) = color {
+            candidates.retain(|piece| piece.color == color);
+        }
+        Box::new(
+            candidates
+                .into_iter()
+                .map(move |piece| self.board_piece(piece))
+                .filter(move |piece| piece.attack_sight(location, check_legal).is_legal()),
+        )
+    }
+
+    /// Gathers every piece that is geometrically able to reach `location`
+    /// (right shape, right line, nothing closer in the way) regardless of
+    /// color or legality; `find_attackers_of` narrows this down further.
+    /// Using the ray index keeps this to the eight ray directions and eight
+    /// knight offsets instead of a scan over every piece on the board.
+    fn attacker_candidates(&self, location: Location) -> Vec<Piece> {
+        let mut candidates = Vec::new();
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let Some(piece) = self.nearest_occupant(location, dx, dy) else {
+                    continue;
+                };
+                let on_diagonal = dx != 0 && dy != 0;
+                let slides_this_way = if on_diagonal {
+                    matches!(piece.shape, Bishop | Queen)
+                } else {
+                    matches!(piece.shape, Rook | Queen)
+                };
+                let adjacent = (piece.location.x() - location.x()).abs() <= 1
+                    && (piece.location.y() - location.y()).abs() <= 1;
+                let forward = pawn_forward(piece.color);
+                let pawn_attacks = on_diagonal && adjacent && piece.shape == Pawn && dy == -forward;
+                if slides_this_way || (adjacent && piece.shape == King) || pawn_attacks {
+                    candidates.push(piece);
+                }
+            }
+        }
+        for (ox, oy) in KNIGHT_OFFSETS {
+            let square = Location(location.x() + ox, location.y() + oy);
+            if let Some(&idx) = self.index.by_location.get(&square) {
+                let piece = self.pieces[idx];
+                if piece.shape == Knight {
+                    candidates.push(piece);
+                }
+            }
         }
+        candidates
+    }
+
+    /// Finds the nearest occupied square from `from` along the ray that
+    /// travels in direction `(dx, dy)`, where each of `dx`/`dy` is `-1`, `0`,
+    /// or `1`. Runs in O(log n) via the ray indices instead of scanning
+    /// every piece on the board.
+    fn nearest_occupant(&self, from: Location, dx: Coordinate, dy: Coordinate) -> Option<Piece> {
+        let idx = if dx == 0 {
+            let column = self.index.by_file.get(&from.x())?;
+            if dy > 0 {
+                column.range(from.y() + 1..).next()
+            } else {
+                column.range(..from.y()).next_back()
+            }
+        } else if dy == 0 {
+            let row = self.index.by_rank.get(&from.y())?;
+            if dx > 0 {
+                row.range(from.x() + 1..).next()
+            } else {
+                row.range(..from.x()).next_back()
+            }
+        } else if dx * dy > 0 {
+            let diagonal = self.index.by_diagonal.get(&(from.x() - from.y()))?;
+            if dx > 0 {
+                diagonal.range(from.x() + 1..).next()
+            } else {
+                diagonal.range(..from.x()).next_back()
+            }
+        } else {
+            let diagonal = self.index.by_antidiagonal.get(&(from.x() + from.y()))?;
+            if dx > 0 {
+                diagonal.range(from.x() + 1..).next()
+            } else {
+                diagonal.range(..from.x()).next_back()
+            }
+        };
+        idx.map(|(_, &i)| self.pieces[i])
     }
 
     fn board_piece(&self, piece: Piece) -> BoardPiece {
         BoardPiece { piece, board: self }
     }
 
+    /// Returns whether moving the piece at `location` to `blocking_at` would
+    /// expose its own king to attack, i.e. whether the piece is pinned.
     fn makes_discovered_attack(&self, location: Location, blocking_at: Location) -> bool {
-        todo!()
+        let Some(mover) = self.piece_at(location) else {
+            return false;
+        };
+        let Some(king) = self
+            .pieces_where(|piece| piece.shape == King && piece.color == mover.color())
+            .next()
+        else {
+            return false;
+        };
+        let king_location = king.location();
+
+        let delta_x = location.x() - king_location.x();
+        let delta_y = location.y() - king_location.y();
+        let is_straight = (delta_x == 0) != (delta_y == 0);
+        let is_diagonal = delta_x != 0 && delta_x.abs() == delta_y.abs();
+        if !is_straight && !is_diagonal {
+            // Not even on a line with its own king, so it can't be pinned.
+            return false;
+        }
+        let dx = delta_x.signum();
+        let dy = delta_y.signum();
+
+        // The nearest occupant out from the king on this line has to be the
+        // mover itself, or this isn't the mover's pin line at all.
+        let Some(first) = self.nearest_occupant(king_location, dx, dy) else {
+            return false;
+        };
+        if first.location != location {
+            return false;
+        }
+
+        // Whatever sits just beyond the mover on the same line is the only
+        // piece that could be pinning it.
+        let Some(pinner) = self.nearest_occupant(location, dx, dy) else {
+            return false;
+        };
+        let pinner_matches_line = if is_diagonal {
+            matches!(pinner.shape, Bishop | Queen)
+        } else {
+            matches!(pinner.shape, Rook | Queen)
+        };
+        if pinner.color == mover.color() || !pinner_matches_line {
+            return false;
+        }
+
+        // Moving anywhere along the same stretch of the line (up to and
+        // including capturing the pinner) still shields the king.
+        !self.lies_between(king_location, pinner.location, blocking_at, dx, dy)
+    }
+
+    /// Returns whether `point` lies on the line through `from` in direction
+    /// `(dx, dy)`, on either side of `from`.
+    fn lies_on_ray(&self, from: Location, point: Location, dx: Coordinate, dy: Coordinate) -> bool {
+        if dx == 0 {
+            point.x() == from.x()
+        } else if dy == 0 {
+            point.y() == from.y()
+        } else {
+            point.x() - from.x() == (point.y() - from.y()) * dx * dy
+        }
+    }
+
+    /// Returns whether `point` lies on the ray from `from` toward `to` in
+    /// direction `(dx, dy)`, strictly after `from` and no further than `to`.
+    fn lies_between(&self, from: Location, to: Location, point: Location, dx: Coordinate, dy: Coordinate) -> bool {
+        if !self.lies_on_ray(from, point, dx, dy) {
+            return false;
+        }
+        if dx != 0 {
+            if dx > 0 { point.x() > from.x() && point.x() <= to.x() } else { point.x() < from.x() && point.x() >= to.x() }
+        } else if dy > 0 {
+            point.y() > from.y() && point.y() <= to.y()
+        } else {
+            point.y() < from.y() && point.y() >= to.y()
+        }
+    }
+
+    /// Returns whether some piece sits strictly between `from` and `to` along
+    /// the ray that travels in direction `(dx, dy)`, where each of `dx`/`dy`
+    /// is `-1`, `0`, or `1`. `from` and `to` must already be known to lie on
+    /// that ray (straight for `dx == 0 || dy == 0`, diagonal otherwise).
+    fn ray_blocked(&self, from: Location, to: Location, dx: Coordinate, dy: Coordinate) -> bool {
+        let Some(piece) = self.nearest_occupant(from, dx, dy) else {
+            return false;
+        };
+        if dx != 0 {
+            if dx > 0 { piece.location.x() < to.x() } else { piece.location.x() > to.x() }
+        } else if dy > 0 {
+            piece.location.y() < to.y()
+        } else {
+            piece.location.y() > to.y()
+        }
+    }
+
+    /// Validates and applies `mv`, relocating the piece, resolving captures
+    /// (including en passant), moving the castling rook, and updating castle
+    /// rights, returning a [`MoveUndo`] that [`Board::unmake_move`] can use
+    /// to restore the position exactly.
+    pub fn apply(&mut self, mv: Move) -> Result<MoveUndo, MoveError> {
+        let Move { from, to, promotion } = mv;
+        let Some(mover) = self.piece_at(from) else {
+            return Err(MoveError::NoPieceAtSource);
+        };
+        let color = mover.color();
+        let shape = mover.shape();
+        if color != self.to_move {
+            return Err(MoveError::WrongColorToMove);
+        }
+
+        let delta_x = to.x() - from.x();
+        let delta_y = to.y() - from.y();
+        if shape == King && delta_y == 0 && delta_x.abs() == 2 {
+            return self.apply_castle(color, from, to, delta_x);
+        }
+
+        // A pawn moving diagonally is capturing, which is its attack square
+        // rather than its move square; everything else moves and captures
+        // onto the same squares.
+        let sight = if shape == Pawn && delta_x != 0 {
+            mover.attack_sight(to, true)
+        } else {
+            mover.move_sight(to, true)
+        };
+        if !sight.is_legal() {
+            return Err(MoveError::IllegalDestination);
+        }
+        let reaches_far_rank = shape == Pawn && to.y() == back_rank_y(color.other());
+        match promotion {
+            Some(new_shape) if shape != Pawn || !reaches_far_rank || matches!(new_shape, Pawn | King) => {
+                return Err(MoveError::IllegalPromotion);
+            }
+            None if reaches_far_rank => return Err(MoveError::IllegalPromotion),
+            _ => {}
+        }
+
+        let captured = self.piece_at(to).map(|piece| piece.piece);
+        if let Some(existing) = captured {
+            if existing.color == color {
+                return Err(MoveError::IllegalDestination);
+            }
+        }
+        let is_en_passant = shape == Pawn && delta_x != 0 && captured.is_none();
+        let passed_pawn = if is_en_passant {
+            self.piece_at(Location(to.x(), from.y())).map(|piece| piece.piece)
+        } else {
+            None
+        };
+        if let Some(passed) = passed_pawn {
+            if passed.color == color {
+                return Err(MoveError::IllegalDestination);
+            }
+        }
+
+        let castle_data = self.castle_data;
+        let en_passant = self.en_passant;
+        match shape {
+            King => self.castle_data.for_color_mut(color).king_moved = true,
+            Rook if from == Location(-4, back_rank_y(color)) => {
+                self.castle_data.for_color_mut(color).queenside_rook_moved = true
+            }
+            Rook if from == Location(3, back_rank_y(color)) => {
+                self.castle_data.for_color_mut(color).kingdside_rook_moved = true
+            }
+            _ => {}
+        }
+        self.en_passant = if shape == Pawn && delta_y.abs() == 2 {
+            Some(Location(from.x(), from.y() + delta_y.signum()))
+        } else {
+            None
+        };
+
+        if let Some(passed) = passed_pawn {
+            self.remove_piece_at(passed.location);
+        } else if captured.is_some() {
+            self.remove_piece_at(to);
+        }
+        self.relocate_piece(from, to);
+        if let Some(new_shape) = promotion {
+            self.set_shape_at(to, new_shape);
+        }
+        self.to_move = self.to_move.other();
+
+        Ok(MoveUndo {
+            mv,
+            captured: captured.or(passed_pawn),
+            castle_data,
+            en_passant,
+            to_move: color,
+            rook_move: None,
+        })
+    }
+
+    /// Reverses a move previously returned by [`Board::apply`], restoring
+    /// the piece, any capture, the castling rook, castle rights, and en
+    /// passant rights.
+    pub fn unmake_move(&mut self, undo: MoveUndo) {
+        let MoveUndo { mv, captured, castle_data, en_passant, to_move, rook_move } = undo;
+        self.relocate_piece(mv.to, mv.from);
+        if mv.promotion.is_some() {
+            self.set_shape_at(mv.from, Pawn);
+        }
+        if let Some((rook_from, rook_to)) = rook_move {
+            self.relocate_piece(rook_to, rook_from);
+        }
+        if let Some(captured) = captured {
+            self.push_piece(captured);
+        }
+        self.castle_data = castle_data;
+        self.en_passant = en_passant;
+        self.to_move = to_move;
+    }
+
+    /// Validates and applies a two-square king move as castling, moving the
+    /// corresponding rook to the square it jumped over.
+    fn apply_castle(&mut self, color: Color, from: Location, to: Location, delta_x: Coordinate) -> Result<MoveUndo, MoveError> {
+        if color != self.to_move {
+            return Err(MoveError::WrongColorToMove);
+        }
+        let kingside = delta_x > 0;
+        let entry = self.castle_data.for_color(color);
+        if entry.king_moved
+            || (kingside && entry.kingdside_rook_moved)
+            || (!kingside && entry.queenside_rook_moved)
+        {
+            return Err(MoveError::IllegalDestination);
+        }
+
+        let rook_from = Location(if kingside { 3 } else { -4 }, from.y());
+        let Some(rook) = self.piece_at(rook_from) else {
+            return Err(MoveError::IllegalDestination);
+        };
+        if rook.shape() != Rook || rook.color() != color {
+            return Err(MoveError::IllegalDestination);
+        }
+
+        let step = delta_x.signum();
+        if self.ray_blocked(from, rook_from, step, 0) {
+            return Err(MoveError::IllegalDestination);
+        }
+
+        // The king may not start, pass through, or land in check.
+        let enemy = Some(color.other());
+        let midpoint = Location(from.x() + step, from.y());
+        for square in [from, midpoint, to] {
+            if self.find_attackers_of(square, false, enemy).next().is_some() {
+                return Err(MoveError::IllegalDestination);
+            }
+            // A pawn's attack sight only covers squares it could capture
+            // onto, so pawns guarding these empty squares are checked here.
+            let attacked_by_pawn = [-1, 1].into_iter().any(|side| {
+                let pawn_square = Location(square.x() + side, square.y() + pawn_forward(color));
+                matches!(self.piece_at(pawn_square), Some(piece) if piece.shape() == Pawn && piece.color() == color.other())
+            });
+            if attacked_by_pawn {
+                return Err(MoveError::IllegalDestination);
+            }
+        }
+
+        let rook_to = Location(to.x() - step, to.y());
+        let castle_data = self.castle_data;
+        let en_passant = self.en_passant;
+        self.castle_data.for_color_mut(color).king_moved = true;
+        self.en_passant = None;
+        self.relocate_piece(from, to);
+        self.relocate_piece(rook_from, rook_to);
+        self.to_move = self.to_move.other();
+
+        Ok(MoveUndo {
+            mv: Move { from, to, promotion: None },
+            captured: None,
+            castle_data,
+            en_passant,
+            to_move: color,
+            rook_move: Some((rook_from, rook_to)),
+        })
+    }
+
+    /// Serializes the position as a FEN-style but coordinate-based notation
+    /// suited to the infinite board: a `;`-separated list of `color/shape@x,y`
+    /// piece tokens, then the side to move, castle rights (`KQkq`-style, `-`
+    /// if none), and the en passant square (`x,y`, or `-` if none).
+    pub fn to_notation(&self) -> String {
+        let pieces = self
+            .pieces
+            .iter()
+            .map(|piece| {
+                format!(
+                    "{}/{}@{},{}",
+                    color_letter(piece.color),
+                    shape_letter(piece.shape),
+                    piece.location.x(),
+                    piece.location.y(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(";");
+        let en_passant = match self.en_passant {
+            Some(location) => format!("{},{}", location.x(), location.y()),
+            None => "-".to_string(),
+        };
+        format!(
+            "{} {} {} {}",
+            pieces,
+            color_letter(self.to_move),
+            format_castle_rights(self.castle_data),
+            en_passant,
+        )
+    }
+
+    /// Parses a position previously produced by [`Board::to_notation`].
+    pub fn from_notation(notation: &str) -> Result<Board, ParseError> {
+        let mut fields = notation.split(' ');
+        let pieces_field = fields.next().ok_or(ParseError::MissingField)?;
+        let to_move_field = fields.next().ok_or(ParseError::MissingField)?;
+        let castle_field = fields.next().ok_or(ParseError::MissingField)?;
+        let en_passant_field = fields.next().ok_or(ParseError::MissingField)?;
+        if fields.next().is_some() {
+            return Err(ParseError::MalformedToken);
+        }
+
+        let mut board = Board::new_blank();
+        let mut seen = HashSet::new();
+        if !pieces_field.is_empty() {
+            for token in pieces_field.split(';') {
+                let piece = parse_piece_token(token)?;
+                if !seen.insert(piece.location) {
+                    return Err(ParseError::DuplicateSquare);
+                }
+                board.push_piece(piece);
+            }
+        }
+
+        board.to_move = parse_color(to_move_field)?;
+        board.castle_data = parse_castle_rights(castle_field)?;
+        board.en_passant = match en_passant_field {
+            "-" => None,
+            square => Some(parse_square(square)?),
+        };
+
+        Ok(board)
+    }
+
+    /// Checks the position for the invariants the rest of the board logic
+    /// assumes but `new_blank` plus [`Board::raw_board`] (or a
+    /// half-assembled [`BoardBuilder`]) doesn't enforce on its own: exactly
+    /// one king per color, no two pieces sharing a square, castle rights
+    /// consistent with where the king and rooks actually are, and the side
+    /// not to move not currently in check.
+    pub fn validate(&self) -> Result<(), InvalidPosition> {
+        let mut seen = HashSet::new();
+        for piece in &self.pieces {
+            if !seen.insert(piece.location) {
+                return Err(InvalidPosition::OverlappingPieces);
+            }
+        }
+
+        for color in [White, Black] {
+            match self
+                .pieces
+                .iter()
+                .filter(|piece| piece.shape == King && piece.color == color)
+                .count()
+            {
+                1 => {}
+                0 => return Err(InvalidPosition::MissingKing(color)),
+                _ => return Err(InvalidPosition::MultipleKings(color)),
+            }
+        }
+
+        for color in [White, Black] {
+            let entry = self.castle_data.for_color(color);
+            let base_y = back_rank_y(color);
+            let has_piece = |location: Location, shape: Shape| {
+                matches!(self.piece_at(location), Some(piece) if piece.shape() == shape && piece.color() == color)
+            };
+            if !entry.king_moved && !has_piece(Location(0, base_y), King) {
+                return Err(InvalidPosition::InconsistentCastleRights(color));
+            }
+            if !entry.kingdside_rook_moved && !has_piece(Location(3, base_y), Rook) {
+                return Err(InvalidPosition::InconsistentCastleRights(color));
+            }
+            if !entry.queenside_rook_moved && !has_piece(Location(-4, base_y), Rook) {
+                return Err(InvalidPosition::InconsistentCastleRights(color));
+            }
+        }
+
+        let defender = self.to_move.other();
+        if let Some(king) = self
+            .pieces_where(|piece| piece.shape == King && piece.color == defender)
+            .next()
+        {
+            let attacked = self
+                .find_attackers_of(king.location(), false, Some(self.to_move))
+                .next()
+                .is_some();
+            if attacked {
+                return Err(InvalidPosition::SideNotToMoveInCheck);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Why [`Board::validate`] rejected a position.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum InvalidPosition {
+    MissingKing(Color),
+    MultipleKings(Color),
+    OverlappingPieces,
+    InconsistentCastleRights(Color),
+    SideNotToMoveInCheck,
+}
+
+/// Accumulates pieces and side-to-move/castle/en-passant state for an
+/// arbitrary position, the safe alternative to `new_blank` plus
+/// [`Board::raw_board`] for constructing custom setups like puzzles. Pair
+/// with [`Board::validate`] before trusting the result, the same
+/// `Setup`/`is_valid` split shakmaty and seer use.
+pub struct BoardBuilder {
+    board: Board,
+}
+
+impl BoardBuilder {
+    pub fn new() -> BoardBuilder {
+        let mut board = Board::new_blank();
+        board.castle_data = CastleData::none();
+        BoardBuilder { board }
+    }
+
+    pub fn piece(mut self, color: Color, shape: Shape, location: Location) -> BoardBuilder {
+        self.board.push_piece(Piece { color, shape, location });
+        self
+    }
+
+    pub fn to_move(mut self, color: Color) -> BoardBuilder {
+        self.board.to_move = color;
+        self
+    }
+
+    pub fn en_passant(mut self, square: Option<Location>) -> BoardBuilder {
+        self.board.en_passant = square;
+        self
+    }
+
+    /// Sets whether `color` can still castle kingside/queenside.
+    pub fn castle_rights(mut self, color: Color, kingside: bool, queenside: bool) -> BoardBuilder {
+        *self.board.castle_data.for_color_mut(color) = CastleDataEntry::from_rights(kingside, queenside);
+        self
+    }
+
+    pub fn build(self) -> Board {
+        self.board
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> BoardBuilder {
+        BoardBuilder::new()
+    }
+}
+
+/// A move from one square to another, with an optional promotion shape for
+/// a pawn reaching the far end of the board.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub from: Location,
+    pub to: Location,
+    pub promotion: Option<Shape>,
+}
+
+/// Why [`Board::apply`] rejected a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    NoPieceAtSource,
+    IllegalDestination,
+    WrongColorToMove,
+    IllegalPromotion,
+}
+
+/// An opaque token returned by [`Board::apply`] that [`Board::unmake_move`]
+/// consumes to restore the position exactly as it was.
+pub struct MoveUndo {
+    mv: Move,
+    captured: Option<Piece>,
+    castle_data: CastleData,
+    en_passant: Option<Location>,
+    to_move: Color,
+    rook_move: Option<(Location, Location)>,
+}
+
+/// Why [`Board::from_notation`] rejected a notation string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    MissingField,
+    MalformedToken,
+    UnknownColor,
+    UnknownShape,
+    InvalidCoordinate,
+    DuplicateSquare,
+}
+
+fn color_letter(color: Color) -> char {
+    match color {
+        White => 'w',
+        Black => 'b',
     }
 }
 
+fn parse_color(s: &str) -> Result<Color, ParseError> {
+    match s {
+        "w" => Ok(White),
+        "b" => Ok(Black),
+        _ => Err(ParseError::UnknownColor),
+    }
+}
+
+fn shape_letter(shape: Shape) -> char {
+    match shape {
+        Pawn => 'P',
+        Rook => 'R',
+        Knight => 'N',
+        Bishop => 'B',
+        Queen => 'Q',
+        King => 'K',
+    }
+}
+
+fn parse_shape(s: &str) -> Result<Shape, ParseError> {
+    match s {
+        "P" => Ok(Pawn),
+        "R" => Ok(Rook),
+        "N" => Ok(Knight),
+        "B" => Ok(Bishop),
+        "Q" => Ok(Queen),
+        "K" => Ok(King),
+        _ => Err(ParseError::UnknownShape),
+    }
+}
+
+fn parse_square(s: &str) -> Result<Location, ParseError> {
+    let (x, y) = s.split_once(',').ok_or(ParseError::MalformedToken)?;
+    let x = x.parse::<Coordinate>().map_err(|_| ParseError::InvalidCoordinate)?;
+    let y = y.parse::<Coordinate>().map_err(|_| ParseError::InvalidCoordinate)?;
+    Ok(Location(x, y))
+}
+
+fn parse_piece_token(token: &str) -> Result<Piece, ParseError> {
+    let (color, rest) = token.split_once('/').ok_or(ParseError::MalformedToken)?;
+    let (shape, square) = rest.split_once('@').ok_or(ParseError::MalformedToken)?;
+    Ok(Piece {
+        color: parse_color(color)?,
+        shape: parse_shape(shape)?,
+        location: parse_square(square)?,
+    })
+}
+
+/// Formats which castling moves are still available as a FEN-style `KQkq`
+/// string (uppercase for white, lowercase for black; `-` if none remain).
+fn format_castle_rights(data: CastleData) -> String {
+    let mut rights = String::new();
+    let white = data.for_color(White);
+    let black = data.for_color(Black);
+    if !white.king_moved && !white.kingdside_rook_moved {
+        rights.push('K');
+    }
+    if !white.king_moved && !white.queenside_rook_moved {
+        rights.push('Q');
+    }
+    if !black.king_moved && !black.kingdside_rook_moved {
+        rights.push('k');
+    }
+    if !black.king_moved && !black.queenside_rook_moved {
+        rights.push('q');
+    }
+    if rights.is_empty() {
+        rights.push('-');
+    }
+    rights
+}
+
+/// Parses a `KQkq`-style castle rights string. Since such a string can only
+/// say whether castling a given side is still available, not specifically
+/// whether the king or that rook moved, a missing flag is attributed to that
+/// side's rook having moved; the king counts as moved only when both of its
+/// color's flags are missing.
+fn parse_castle_rights(s: &str) -> Result<CastleData, ParseError> {
+    let (mut white_kingside, mut white_queenside) = (false, false);
+    let (mut black_kingside, mut black_queenside) = (false, false);
+    if s != "-" {
+        for flag in s.chars() {
+            match flag {
+                'K' => white_kingside = true,
+                'Q' => white_queenside = true,
+                'k' => black_kingside = true,
+                'q' => black_queenside = true,
+                _ => return Err(ParseError::MalformedToken),
+            }
+        }
+    }
+    Ok(CastleData {
+        white: CastleDataEntry::from_rights(white_kingside, white_queenside),
+        black: CastleDataEntry::from_rights(black_kingside, black_queenside),
+    })
+}
+
 #[derive(Clone, Copy)]
 pub struct BoardPiece<'a> {
     piece: Piece,
@@ -238,65 +1123,87 @@ impl<'a> BoardPiece<'a> {
         let makes_discovered_attack = || check_legal && self.board.makes_discovered_attack(location, destination);
         match self.shape() {
             Pawn => {
-                if destination.1 != location.1 {
+                // Straight pushes only; diagonal captures (including en
+                // passant) are the pawn's attack squares, not its move
+                // squares, so they're handled by `attack_sight` instead.
+                if destination.x() != location.x() {
                     return Sight::CannotSee;
                 }
-                let is_in_front = match self.color() {
-                    White => destination.1 - location.0 == 1,
-                    Black => destination.1 - location.0 == -1,
-                };
-                if !is_in_front {
+                let forward = pawn_forward(self.color());
+                let delta_y = destination.y() - location.y();
+                let is_double_push = delta_y == forward * 2 && location.y() == pawn_start_y(self.color());
+                if delta_y != forward && !is_double_push {
+                    return Sight::CannotSee;
+                }
+                if self.board.piece_at(destination).is_some() {
+                    // Pawns can't push onto an occupied square.
                     return Sight::CannotSee;
                 }
+                if is_double_push {
+                    let intermediate = Location(location.x(), location.y() + forward);
+                    if self.board.piece_at(intermediate).is_some() {
+                        return Sight::CannotSee;
+                    }
+                }
                 illegal = makes_discovered_attack();
             }
             Rook => {
-                macro_rules! block_check {
-                    ($a:ident, $v:ident) => {
-                        // Check for pieces blocking the view.
-                        if destination.$v() > location.$v() {
-                            for piece in &self.board.pieces {
-                                // Checks if piece is between here and the destination.
-                                if piece.location.$a() == destination.$a() && piece.location.$v() > location.$v() && piece.location.$v() < destination.$v() {
-                                    return Sight::CannotSee;
-                                }
-                            }
-                        } else {
-                            for piece in &self.board.pieces {
-                                // Checks if piece is between here and the destination.
-                                if piece.location.$a() == destination.$a() && piece.location.$v() < location.$v() && piece.location.$v() > destination.$v() {
-                                    return Sight::CannotSee;
-                                }
-                            }
-                        }
-                    };
+                let delta_x = destination.x() - location.x();
+                let delta_y = destination.y() - location.y();
+                if (delta_x == 0) == (delta_y == 0) {
+                    // Either it doesn't move, or it moves off the rank/file.
+                    return Sight::CannotSee;
                 }
-                if location.0 == destination.0 {
-                    block_check!(x, y);
-                } else if location.1 == destination.1 {
-                    block_check!(y, x);
-                } else {
+                let dx = delta_x.signum();
+                let dy = delta_y.signum();
+                if self.board.ray_blocked(location, destination, dx, dy) {
                     return Sight::CannotSee;
                 }
                 illegal = makes_discovered_attack();
             }
             Knight => {
-                todo!();
+                let delta_x = destination.x() - location.x();
+                let delta_y = destination.y() - location.y();
+                let is_knight_jump = matches!((delta_x.abs(), delta_y.abs()), (1, 2) | (2, 1));
+                if !is_knight_jump {
+                    return Sight::CannotSee;
+                }
                 illegal = makes_discovered_attack();
             }
             Bishop => {
-                todo!();
+                let delta_x = destination.x() - location.x();
+                let delta_y = destination.y() - location.y();
+                if delta_x == 0 || delta_x.abs() != delta_y.abs() {
+                    return Sight::CannotSee;
+                }
+                let dx = delta_x.signum();
+                let dy = delta_y.signum();
+                if self.board.ray_blocked(location, destination, dx, dy) {
+                    return Sight::CannotSee;
+                }
                 illegal = makes_discovered_attack();
             }
             Queen => {
-                todo!();
+                let delta_x = destination.x() - location.x();
+                let delta_y = destination.y() - location.y();
+                let is_straight = (delta_x == 0) != (delta_y == 0);
+                let is_diagonal = delta_x != 0 && delta_x.abs() == delta_y.abs();
+                if !is_straight && !is_diagonal {
+                    return Sight::CannotSee;
+                }
+                let dx = delta_x.signum();
+                let dy = delta_y.signum();
+                if self.board.ray_blocked(location, destination, dx, dy) {
+                    return Sight::CannotSee;
+                }
                 illegal = makes_discovered_attack();
             }
             King => {
-                let delta_x = destination.0 - location.0;
-                if delta_x != 1 && delta_x != -1 { return Sight::CannotSee; }
-                let delta_y = destination.1 - location.1;
-                if delta_y != 1 && delta_y != -1 { return Sight::CannotSee; }
+                let delta_x = destination.x() - location.x();
+                let delta_y = destination.y() - location.y();
+                if delta_x.abs() > 1 || delta_y.abs() > 1 || (delta_x == 0 && delta_y == 0) {
+                    return Sight::CannotSee;
+                }
 
                 illegal = check_legal && self.board.find_attackers_of(destination, false, Some(self.color().other())).next().is_some();
             }
@@ -311,6 +1218,362 @@ impl<'a> BoardPiece<'a> {
     }
 
     pub fn attack_sight(self, destination: Location, check_legal: bool) -> Sight<'a> {
-        todo!()
+        match self.shape() {
+            // Pawns attack diagonally rather than along their move square, so
+            // their move-based sight doesn't apply here.
+            Pawn => {
+                let location = self.location();
+                let delta_x = destination.x() - location.x();
+                let delta_y = destination.y() - location.y();
+                if delta_x.abs() != 1 || delta_y != pawn_forward(self.color()) {
+                    return Sight::CannotSee;
+                }
+                let illegal = check_legal && self.board.makes_discovered_attack(location, destination);
+                match self.board.piece_at(destination) {
+                    Some(piece) => {
+                        if illegal { Sight::IllegalSees(piece) } else { Sight::Sees(piece) }
+                    }
+                    // An empty square is only attacked en passant, capturing
+                    // the pawn that just double-pushed past it.
+                    None if self.board.en_passant == Some(destination) => {
+                        if illegal { Sight::IllegalSeesEmpty } else { Sight::SeesEmpty }
+                    }
+                    None => Sight::CannotSee,
+                }
+            }
+            // Every other piece attacks exactly the squares it can move to,
+            // captures included, so its sight is the same as its move sight.
+            _ => self.move_sight(destination, check_legal),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board_with(pieces: &[(Color, Shape, Coordinate, Coordinate)]) -> Board {
+        let mut builder = BoardBuilder::new();
+        for &(color, shape, x, y) in pieces {
+            builder = builder.piece(color, shape, Location::new(x, y));
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn pinned_rook_cannot_step_off_the_pin_line() {
+        let board = board_with(&[
+            (White, King, 0, 0),
+            (White, Rook, 0, 2),
+            (Black, Rook, 0, 5),
+            (Black, King, 5, 5),
+        ]);
+        let rook = board.piece_at(Location::new(0, 2)).unwrap();
+        assert!(!rook.move_sight(Location::new(1, 2), true).is_legal());
+        assert!(rook.move_sight(Location::new(0, 3), true).is_legal());
+        assert!(rook.move_sight(Location::new(0, 5), true).is_legal());
+    }
+
+    #[test]
+    fn unpinned_rook_moves_freely() {
+        let board = board_with(&[
+            (White, King, 0, 0),
+            (White, Rook, 2, 2),
+            (Black, Rook, 0, 5),
+            (Black, King, 5, 5),
+        ]);
+        let rook = board.piece_at(Location::new(2, 2)).unwrap();
+        assert!(rook.move_sight(Location::new(5, 2), true).is_legal());
+    }
+
+    #[test]
+    fn apply_and_unmake_round_trips_a_normal_move() {
+        let mut board = Board::new();
+        let notation_before = board.to_notation();
+        let mv = Move { from: Location::new(0, -3), to: Location::new(0, -1), promotion: None };
+        let undo = board.apply(mv).unwrap();
+        assert!(board.to_move() == Black);
+        board.unmake_move(undo);
+        assert_eq!(board.to_notation(), notation_before);
+    }
+
+    #[test]
+    fn apply_rejects_moving_out_of_turn() {
+        let mut board = Board::new();
+        let mv = Move { from: Location::new(0, 2), to: Location::new(0, 1), promotion: None };
+        assert!(matches!(board.apply(mv), Err(MoveError::WrongColorToMove)));
+    }
+
+    #[test]
+    fn apply_rejects_promotion_on_a_non_pawn() {
+        let mut board = board_with(&[
+            (White, King, 0, 0),
+            (White, Rook, 0, 1),
+            (Black, King, 5, 5),
+        ]);
+        let mv = Move { from: Location::new(0, 1), to: Location::new(0, 2), promotion: Some(King) };
+        assert!(matches!(board.apply(mv), Err(MoveError::IllegalPromotion)));
+    }
+
+    #[test]
+    fn apply_only_revokes_castle_rights_from_the_rooks_home_square() {
+        let mut board = BoardBuilder::new()
+            .piece(White, King, Location::new(0, -4))
+            .piece(White, Rook, Location::new(-4, 10))
+            .piece(Black, King, Location::new(5, 5))
+            .castle_rights(White, true, true)
+            .build();
+        let mv = Move { from: Location::new(-4, 10), to: Location::new(-4, 11), promotion: None };
+        board.apply(mv).unwrap();
+        assert_eq!(board.to_notation().split(' ').nth(2).unwrap(), "KQ");
+    }
+
+    #[test]
+    fn apply_rejects_capturing_a_piece_of_the_same_color() {
+        let mut board = board_with(&[
+            (White, King, 0, 0),
+            (White, Rook, 0, 1),
+            (White, Pawn, 0, 2),
+            (Black, King, 5, 5),
+        ]);
+        let mv = Move { from: Location::new(0, 1), to: Location::new(0, 2), promotion: None };
+        assert!(matches!(board.apply(mv), Err(MoveError::IllegalDestination)));
+    }
+
+    #[test]
+    fn apply_rejects_en_passant_onto_a_friendly_pawn() {
+        let mut board = BoardBuilder::new()
+            .piece(White, King, Location::new(0, 0))
+            .piece(White, Pawn, Location::new(0, 4))
+            .piece(White, Pawn, Location::new(1, 4))
+            .piece(Black, King, Location::new(5, 5))
+            .en_passant(Some(Location::new(1, 5)))
+            .build();
+        let mv = Move { from: Location::new(0, 4), to: Location::new(1, 5), promotion: None };
+        assert!(matches!(board.apply(mv), Err(MoveError::IllegalDestination)));
+    }
+
+    #[test]
+    fn apply_requires_a_promotion_shape_when_a_pawn_reaches_the_far_rank() {
+        let mut board = board_with(&[
+            (White, King, 0, 0),
+            (White, Pawn, 0, 2),
+            (Black, King, 5, 5),
+        ]);
+        let mv = Move { from: Location::new(0, 2), to: Location::new(0, 3), promotion: None };
+        assert!(matches!(board.apply(mv), Err(MoveError::IllegalPromotion)));
+    }
+
+    #[test]
+    fn apply_rejects_promotion_away_from_the_far_rank() {
+        let mut board = board_with(&[
+            (White, King, 0, 0),
+            (White, Pawn, 0, 1),
+            (Black, King, 5, 5),
+        ]);
+        let mv = Move { from: Location::new(0, 1), to: Location::new(0, 2), promotion: Some(Queen) };
+        assert!(matches!(board.apply(mv), Err(MoveError::IllegalPromotion)));
+    }
+
+    #[test]
+    fn apply_promotes_a_pawn_reaching_the_far_rank() {
+        let mut board = board_with(&[
+            (White, King, 0, 0),
+            (White, Pawn, 0, 2),
+            (Black, King, 5, 5),
+        ]);
+        let mv = Move { from: Location::new(0, 2), to: Location::new(0, 3), promotion: Some(Queen) };
+        board.apply(mv).unwrap();
+        assert!(board.piece_at(Location::new(0, 3)).unwrap().shape() == Queen);
+    }
+
+    #[test]
+    fn notation_round_trips_the_standard_setup() {
+        let board = Board::new();
+        let notation = board.to_notation();
+        let restored = Board::from_notation(&notation).unwrap();
+        assert_eq!(restored.to_notation(), notation);
+    }
+
+    #[test]
+    fn notation_round_trips_a_custom_position_with_en_passant() {
+        let board = BoardBuilder::new()
+            .piece(White, King, Location::new(0, -4))
+            .piece(Black, King, Location::new(5, 5))
+            .piece(White, Pawn, Location::new(2, 4))
+            .en_passant(Some(Location::new(2, 3)))
+            .to_move(Black)
+            .build();
+        let notation = board.to_notation();
+        let restored = Board::from_notation(&notation).unwrap();
+        assert_eq!(restored.to_notation(), notation);
+    }
+
+    #[test]
+    fn knight_move_sight_only_allows_l_shaped_jumps() {
+        let board = board_with(&[
+            (White, King, 0, 0),
+            (White, Knight, 3, 3),
+            (Black, King, 7, 7),
+        ]);
+        let knight = board.piece_at(Location::new(3, 3)).unwrap();
+        assert!(knight.move_sight(Location::new(4, 5), true).is_legal());
+        assert!(knight.move_sight(Location::new(5, 4), true).is_legal());
+        assert!(!knight.move_sight(Location::new(4, 4), true).is_legal());
+        assert!(!knight.move_sight(Location::new(3, 5), true).is_legal());
+    }
+
+    #[test]
+    fn bishop_move_sight_is_blocked_by_an_intervening_piece() {
+        let board = board_with(&[
+            (White, King, 0, 0),
+            (White, Bishop, 0, 1),
+            (White, Pawn, 2, 3),
+            (Black, King, 7, 7),
+        ]);
+        let bishop = board.piece_at(Location::new(0, 1)).unwrap();
+        assert!(bishop.move_sight(Location::new(1, 2), true).is_legal());
+        assert!(!bishop.move_sight(Location::new(3, 4), true).is_legal());
+    }
+
+    #[test]
+    fn queen_move_sight_allows_straight_and_diagonal_but_not_knight_jumps() {
+        let board = board_with(&[
+            (White, King, 0, 0),
+            (White, Queen, 2, 2),
+            (Black, King, 7, 7),
+        ]);
+        let queen = board.piece_at(Location::new(2, 2)).unwrap();
+        assert!(queen.move_sight(Location::new(2, 6), true).is_legal());
+        assert!(queen.move_sight(Location::new(5, 5), true).is_legal());
+        assert!(!queen.move_sight(Location::new(4, 3), true).is_legal());
+    }
+
+    #[test]
+    fn nearest_occupant_finds_the_closest_piece_along_a_ray() {
+        let board = board_with(&[
+            (White, King, 0, 0),
+            (White, Pawn, 0, 3),
+            (White, Pawn, 0, 6),
+            (Black, King, 7, 7),
+        ]);
+        let nearest = board.nearest_occupant(Location::new(0, 0), 0, 1).unwrap();
+        assert!(nearest.location == Location::new(0, 3));
+        let nearest = board.nearest_occupant(Location::new(0, 4), 0, 1).unwrap();
+        assert!(nearest.location == Location::new(0, 6));
+        assert!(board.nearest_occupant(Location::new(0, 7), 0, 1).is_none());
+    }
+
+    #[test]
+    fn nearest_occupant_searches_diagonals_and_antidiagonals_independently() {
+        let board = board_with(&[
+            (White, King, 0, 0),
+            (White, Pawn, 3, 3),
+            (White, Pawn, 3, -3),
+            (Black, King, 7, 7),
+        ]);
+        let diagonal = board.nearest_occupant(Location::new(0, 0), 1, 1).unwrap();
+        assert!(diagonal.location == Location::new(3, 3));
+        let antidiagonal = board.nearest_occupant(Location::new(0, 0), 1, -1).unwrap();
+        assert!(antidiagonal.location == Location::new(3, -3));
+    }
+
+    #[test]
+    fn apply_sets_en_passant_square_on_a_double_push() {
+        let mut board = Board::new();
+        let mv = Move { from: Location::new(0, -3), to: Location::new(0, -1), promotion: None };
+        board.apply(mv).unwrap();
+        assert_eq!(board.to_notation().split(' ').nth(3).unwrap(), "0,-2");
+    }
+
+    #[test]
+    fn apply_rejects_a_pawn_double_push_from_off_its_start_square() {
+        let mut board = board_with(&[
+            (White, King, 0, 0),
+            (White, Pawn, 0, 1),
+            (Black, King, 7, 7),
+        ]);
+        let mv = Move { from: Location::new(0, 1), to: Location::new(0, 3), promotion: None };
+        assert!(matches!(board.apply(mv), Err(MoveError::IllegalDestination)));
+    }
+
+    #[test]
+    fn apply_executes_an_en_passant_capture() {
+        let mut board = BoardBuilder::new()
+            .piece(White, King, Location::new(5, 5))
+            .piece(Black, King, Location::new(-5, -5))
+            .piece(White, Pawn, Location::new(0, 0))
+            .piece(Black, Pawn, Location::new(1, 2))
+            .to_move(Black)
+            .build();
+
+        let double_push = Move { from: Location::new(1, 2), to: Location::new(1, 0), promotion: None };
+        board.apply(double_push).unwrap();
+        assert_eq!(board.to_notation().split(' ').nth(3).unwrap(), "1,1");
+
+        let capture = Move { from: Location::new(0, 0), to: Location::new(1, 1), promotion: None };
+        board.apply(capture).unwrap();
+        assert!(board.piece_at(Location::new(1, 0)).is_none());
+        assert!(board.piece_at(Location::new(1, 1)).unwrap().shape() == Pawn);
+    }
+
+    #[test]
+    fn validate_accepts_a_well_formed_custom_position() {
+        let board = BoardBuilder::new()
+            .piece(White, King, Location::new(0, 0))
+            .piece(Black, King, Location::new(5, 5))
+            .build();
+        assert!(board.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_a_missing_king() {
+        let board = BoardBuilder::new().piece(Black, King, Location::new(5, 5)).build();
+        assert!(matches!(board.validate(), Err(InvalidPosition::MissingKing(White))));
+    }
+
+    #[test]
+    fn validate_rejects_multiple_kings() {
+        let board = BoardBuilder::new()
+            .piece(White, King, Location::new(0, 0))
+            .piece(White, King, Location::new(1, 1))
+            .piece(Black, King, Location::new(5, 5))
+            .build();
+        assert!(matches!(board.validate(), Err(InvalidPosition::MultipleKings(White))));
+    }
+
+    #[test]
+    fn validate_rejects_overlapping_pieces() {
+        let board = BoardBuilder::new()
+            .piece(White, King, Location::new(0, 0))
+            .piece(Black, King, Location::new(5, 5))
+            .piece(White, Pawn, Location::new(2, 2))
+            .piece(Black, Pawn, Location::new(2, 2))
+            .build();
+        assert!(matches!(board.validate(), Err(InvalidPosition::OverlappingPieces)));
+    }
+
+    #[test]
+    fn validate_rejects_castle_rights_without_the_rook_present() {
+        let board = BoardBuilder::new()
+            .piece(White, King, Location::new(0, -4))
+            .piece(Black, King, Location::new(5, 5))
+            .castle_rights(White, true, true)
+            .build();
+        assert!(matches!(
+            board.validate(),
+            Err(InvalidPosition::InconsistentCastleRights(White))
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_leaving_the_side_not_to_move_in_check() {
+        let board = BoardBuilder::new()
+            .piece(White, King, Location::new(0, 0))
+            .piece(Black, King, Location::new(5, 5))
+            .piece(White, Rook, Location::new(5, 0))
+            .to_move(White)
+            .build();
+        assert!(matches!(board.validate(), Err(InvalidPosition::SideNotToMoveInCheck)));
     }
 }
\ No newline at end of file